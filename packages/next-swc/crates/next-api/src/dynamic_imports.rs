@@ -1,17 +1,20 @@
+use std::{borrow::Cow, collections::HashMap};
+
 use anyhow::Result;
-use indexmap::IndexMap;
-use turbo_tasks::{
-    graph::{GraphTraversal, NonDeterministic},
-    Value, Vc,
-};
+use indexmap::{IndexMap, IndexSet};
+use turbo_tasks::{Value, Vc};
 use turbopack_binding::{
-    swc::core::ecma::{
-        ast::{CallExpr, Callee, Expr, Ident, Lit},
-        visit::{Visit, VisitWith},
+    swc::core::{
+        common::{comments::Comments, Spanned},
+        ecma::{
+            ast::{CallExpr, Callee, Expr, Ident, Lit, Prop, PropName, PropOrSpread, Tpl},
+            visit::{Visit, VisitWith},
+        },
     },
     turbo::tasks_fs::FileSystemPath,
     turbopack::{
         core::{
+            chunk::ChunkingContext,
             ident::AssetIdent,
             issue::{Issue, IssueExt, IssueSeverity, OptionIssueSource},
             module::Module,
@@ -29,6 +32,7 @@ use turbopack_binding::{
 
 pub(crate) async fn collect_next_dynamic_imports(
     entry: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+    import_map: Vc<DynamicImportMap>,
 ) -> Result<IndexMap<Vc<Box<dyn Module>>, DynamicImportedModules>> {
     // Traverse referenced modules graph, collect all of the dynamic imports:
     // - Read the Program AST of the Module, this is the origin (A)
@@ -37,14 +41,15 @@ pub(crate) async fn collect_next_dynamic_imports(
     // Returned import mappings are in the form of
     // (Module<A>, Vec<(B, Module<B>)>) (where B is the raw import source string,
     // and Module<B> is the actual resolved Module)
-    let imported_modules_mapping = NonDeterministic::new()
-        .skip_duplicates()
-        .visit([Vc::upcast(entry)], get_referenced_modules)
-        .await
-        .completed()?
-        .into_inner()
+    //
+    // Traversal order matters here: it determines the order entries land in the
+    // `IndexMap` below, and therefore the resulting chunk layout. We can't use a
+    // non-deterministic visitor and expect stable output, so we run our own
+    // cycle-aware, deterministic walk instead (see `collect_modules_with_scc_ordering`).
+    let imported_modules_mapping = collect_modules_with_scc_ordering(Vc::upcast(entry))
+        .await?
         .into_iter()
-        .map(build_dynamic_imports_map_for_module);
+        .map(|module| build_dynamic_imports_map_for_module(module, import_map));
 
     // Consolidate import mappings into a single indexmap
     let mut import_mappings: IndexMap<Vc<Box<dyn Module>>, DynamicImportedModules> =
@@ -63,6 +68,30 @@ pub(crate) async fn collect_next_dynamic_imports(
     Ok(import_mappings)
 }
 
+/// Builds the output chunks for every dynamic import collected by
+/// [collect_next_dynamic_imports], naming each one after `webpackChunkName` when the
+/// author set one, falling back to the raw import source otherwise.
+pub(crate) async fn collect_next_dynamic_chunks(
+    entry: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+    import_map: Vc<DynamicImportMap>,
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+) -> Result<Vc<DynamicImportedChunks>> {
+    let import_mappings = collect_next_dynamic_imports(entry, import_map).await?;
+
+    let mut chunks: IndexMap<Vc<Box<dyn Module>>, DynamicImportedOutputAssets> = IndexMap::new();
+    for (origin_module, dynamic_imports) in import_mappings {
+        let mut output_assets = Vec::with_capacity(dynamic_imports.len());
+        for (source, _attributes, chunk_options, module) in dynamic_imports {
+            let chunk_group = chunking_context.root_chunk_group(module).await?;
+            let name = chunk_options.chunk_name.clone().unwrap_or(source);
+            output_assets.push((name, chunk_options, chunk_group.assets));
+        }
+        chunks.insert(origin_module, output_assets);
+    }
+
+    Ok(Vc::cell(chunks))
+}
+
 async fn get_referenced_modules(
     parent: Vc<Box<dyn Module>>,
 ) -> Result<impl Iterator<Item = Vc<Box<dyn Module>>> + Send> {
@@ -71,17 +100,159 @@ async fn get_referenced_modules(
         .map(|modules| modules.clone_value().into_iter())
 }
 
+/// Groups the module reference graph from `entry` into strongly connected components,
+/// emitted in reverse-topological order, with cyclic modules sorted by `AssetIdent` path
+/// for a stable result. Flattens the graph into a plain adjacency list and hands the SCC
+/// computation itself off to [tarjan_scc].
+async fn collect_modules_with_scc_ordering(
+    entry: Vc<Box<dyn Module>>,
+) -> Result<Vec<Vc<Box<dyn Module>>>> {
+    let mut index_of: HashMap<Vc<Box<dyn Module>>, usize> = HashMap::new();
+    let mut modules: Vec<Vc<Box<dyn Module>>> = Vec::new();
+    let mut edges: Vec<Vec<usize>> = Vec::new();
+    let mut to_visit = vec![entry];
+
+    index_of.insert(entry, 0);
+    modules.push(entry);
+    edges.push(Vec::new());
+
+    while let Some(node) = to_visit.pop() {
+        let node_index = index_of[&node];
+        let children: IndexSet<_> = get_referenced_modules(node).await?.collect();
+
+        for child in children {
+            let child_index = *index_of.entry(child).or_insert_with(|| {
+                modules.push(child);
+                edges.push(Vec::new());
+                to_visit.push(child);
+                modules.len() - 1
+            });
+            edges[node_index].push(child_index);
+        }
+    }
+
+    let mut ordered = Vec::new();
+    for component in tarjan_scc(&edges, 0) {
+        let mut keyed = Vec::with_capacity(component.len());
+        for index in component {
+            let module = modules[index];
+            keyed.push((module.ident().path().await?.path.clone(), module));
+        }
+        keyed.sort_by(|(a, _), (b, _)| a.cmp(b));
+        ordered.extend(keyed.into_iter().map(|(_, module)| module));
+    }
+
+    Ok(ordered)
+}
+
+/// Per-node bookkeeping for Tarjan's algorithm.
+struct TarjanNodeState {
+    index: usize,
+    lowlink: usize,
+    on_stack: bool,
+}
+
+/// Iterative Tarjan's algorithm over `edges` (`edges[i]` lists node `i`'s out-neighbors),
+/// reachable from `start`. Returns components in reverse-topological order.
+fn tarjan_scc(edges: &[Vec<usize>], start: usize) -> Vec<Vec<usize>> {
+    enum Frame {
+        Enter(usize),
+        Exit(usize, Vec<(usize, bool)>),
+    }
+
+    let mut index_counter = 0usize;
+    let mut states: HashMap<usize, TarjanNodeState> = HashMap::new();
+    let mut stack: Vec<usize> = Vec::new();
+    let mut components: Vec<Vec<usize>> = Vec::new();
+    let mut work: Vec<Frame> = vec![Frame::Enter(start)];
+
+    while let Some(frame) = work.pop() {
+        match frame {
+            Frame::Enter(node) => {
+                if states.contains_key(&node) {
+                    continue;
+                }
+
+                states.insert(
+                    node,
+                    TarjanNodeState {
+                        index: index_counter,
+                        lowlink: index_counter,
+                        on_stack: true,
+                    },
+                );
+                index_counter += 1;
+                stack.push(node);
+
+                // Whether each child is a tree edge (first time we've seen it - we're
+                // about to recurse into it) or a back/cross edge to an already-visited
+                // node, decided now while the "already visited" check is still accurate.
+                let child_info: Vec<_> = edges[node]
+                    .iter()
+                    .map(|&child| (child, !states.contains_key(&child)))
+                    .collect();
+
+                work.push(Frame::Exit(node, child_info.clone()));
+                for (child, is_tree_edge) in child_info.into_iter().rev() {
+                    if is_tree_edge {
+                        work.push(Frame::Enter(child));
+                    }
+                }
+            }
+            Frame::Exit(node, child_info) => {
+                for (child, is_tree_edge) in child_info {
+                    let child_state = &states[&child];
+                    let candidate = if is_tree_edge {
+                        child_state.lowlink
+                    } else if child_state.on_stack {
+                        child_state.index
+                    } else {
+                        continue;
+                    };
+
+                    let node_state = states.get_mut(&node).unwrap();
+                    node_state.lowlink = node_state.lowlink.min(candidate);
+                }
+
+                let node_state = &states[&node];
+                if node_state.lowlink == node_state.index {
+                    let mut component = Vec::new();
+                    while let Some(top) = stack.pop() {
+                        states.get_mut(&top).unwrap().on_stack = false;
+                        component.push(top);
+                        if top == node {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+    }
+
+    // Tarjan emits components in the order their DFS subtree fully completes, which is
+    // already reverse-topological (a component's dependencies finish, and get emitted,
+    // before the component itself does).
+    components
+}
+
 #[turbo_tasks::function]
 async fn build_dynamic_imports_map_for_module(
     module: Vc<Box<dyn Module>>,
+    import_map: Vc<DynamicImportMap>,
 ) -> Result<Vc<OptionDynamicImportsMap>> {
+    let import_map = import_map.await?;
+
     let Some(ecmascript_asset) =
         Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await?
     else {
         return Ok(OptionDynamicImportsMap::none());
     };
 
-    let ParseResult::Ok { program, .. } = &*ecmascript_asset.parse().await? else {
+    let ParseResult::Ok {
+        program, comments, ..
+    } = &*ecmascript_asset.parse().await?
+    else {
         NextDynamicParsingIssue {
             ident: module.ident(),
         }
@@ -93,32 +264,76 @@ async fn build_dynamic_imports_map_for_module(
 
     // Reading the Program AST, collect raw imported module str if it's wrapped in
     // dynamic()
-    let mut visitor = LodableImportVisitor::new();
+    let mut visitor = LodableImportVisitor::new(comments);
     program.visit_with(&mut visitor);
 
     if visitor.import_sources.is_empty() {
         return Ok(OptionDynamicImportsMap::none());
     }
 
+    let importer_path = module.ident().path().await?.path.clone();
+
     let mut import_sources = vec![];
-    for import in visitor.import_sources.drain(..) {
+    for mut import in visitor.import_sources.drain(..) {
+        // Bare/aliased specifiers (`#components/hero`, `@/lib/x`, ...) only resolve
+        // correctly if the underlying resolver already knows about them. Rewrite them
+        // through the project's import map first, the same way static imports are, so
+        // dynamic imports stay consistent with the rest of the app. Only constant
+        // specifiers are eligible (a string literal, or a template literal without
+        // interpolations) - an interpolated template literal's constant prefix isn't a
+        // full specifier the import map can match against.
+        if let Pattern::Constant(specifier) = &import.pattern {
+            let rewritten = import_map.resolve(&importer_path, specifier);
+            if rewritten.as_ref() != specifier.as_str() {
+                import.pattern = Pattern::Constant(rewritten.into_owned());
+            }
+        }
+
+        let reference_subtype = match import
+            .attributes
+            .as_ref()
+            .and_then(|attributes| attributes.get("type"))
+        {
+            Some(attribute_type) if attribute_type == "json" => {
+                EcmaScriptModulesReferenceSubType::Custom(attribute_type.as_str().into())
+            }
+            Some(unsupported_type) => {
+                UnsupportedImportAttributeIssue {
+                    ident: module.ident(),
+                    attribute_type: unsupported_type.clone(),
+                }
+                .cell()
+                .emit();
+
+                EcmaScriptModulesReferenceSubType::Undefined
+            }
+            None => EcmaScriptModulesReferenceSubType::Undefined,
+        };
+
         // Using the given `Module` which is the origin of the dynamic import, trying to
-        // resolve the module that is being imported.
-        let dynamic_imported_resolved_module = *esm_resolve(
+        // resolve the module that is being imported. A template-literal import resolves
+        // to a `Pattern` with `Dynamic` segments, which `esm_resolve` can expand into
+        // multiple matching modules, so every match is recorded against the same source.
+        let resolved_modules = esm_resolve(
             Vc::upcast(PlainResolveOrigin::new(
                 ecmascript_asset.await?.asset_context,
                 module.ident().path(),
             )),
-            Request::parse(Value::new(Pattern::Constant(import.to_string()))),
-            Value::new(EcmaScriptModulesReferenceSubType::Undefined),
+            Request::parse(Value::new(import.pattern.clone())),
+            Value::new(reference_subtype),
             OptionIssueSource::none(),
             IssueSeverity::Error.cell(),
         )
-        .first_module()
+        .primary_modules()
         .await?;
 
-        if let Some(dynamic_imported_resolved_module) = dynamic_imported_resolved_module {
-            import_sources.push((import, dynamic_imported_resolved_module));
+        for dynamic_imported_resolved_module in resolved_modules.iter() {
+            import_sources.push((
+                import.source.clone(),
+                import.attributes.clone(),
+                import.chunk_options.clone(),
+                *dynamic_imported_resolved_module,
+            ));
         }
     }
 
@@ -127,21 +342,23 @@ async fn build_dynamic_imports_map_for_module(
 
 /// A visitor to check if there's import to `next/dynamic`, then collecting the
 /// import wrapped with dynamic() via CollectImportSourceVisitor.
-struct LodableImportVisitor {
+struct LodableImportVisitor<'a> {
     dynamic_ident: Option<Ident>,
-    pub import_sources: Vec<String>,
+    comments: &'a dyn Comments,
+    pub import_sources: Vec<CollectedDynamicImport>,
 }
 
-impl LodableImportVisitor {
-    fn new() -> Self {
+impl<'a> LodableImportVisitor<'a> {
+    fn new(comments: &'a dyn Comments) -> Self {
         Self {
             import_sources: vec![],
             dynamic_ident: None,
+            comments,
         }
     }
 }
 
-impl Visit for LodableImportVisitor {
+impl Visit for LodableImportVisitor<'_> {
     fn visit_import_decl(&mut self, decl: &turbopack_binding::swc::core::ecma::ast::ImportDecl) {
         // find import decl from next/dynamic, i.e import dynamic from 'next/dynamic'
         if decl.src.value == *"next/dynamic" {
@@ -157,7 +374,8 @@ impl Visit for LodableImportVisitor {
             if let Expr::Ident(ident) = &**ident {
                 if let Some(dynamic_ident) = &self.dynamic_ident {
                     if ident.sym == *dynamic_ident.sym {
-                        let mut collect_import_source_visitor = CollectImportSourceVisitor::new();
+                        let mut collect_import_source_visitor =
+                            CollectImportSourceVisitor::new(self.comments);
                         call_expr.visit_children_with(&mut collect_import_source_visitor);
 
                         if let Some(import_source) = collect_import_source_visitor.import_source {
@@ -173,30 +391,50 @@ impl Visit for LodableImportVisitor {
 }
 
 /// A visitor to collect import source string from import('path/to/module')
-struct CollectImportSourceVisitor {
-    import_source: Option<String>,
+struct CollectImportSourceVisitor<'a> {
+    comments: &'a dyn Comments,
+    import_source: Option<CollectedDynamicImport>,
 }
 
-impl CollectImportSourceVisitor {
-    fn new() -> Self {
+impl<'a> CollectImportSourceVisitor<'a> {
+    fn new(comments: &'a dyn Comments) -> Self {
         Self {
+            comments,
             import_source: None,
         }
     }
 }
 
-impl Visit for CollectImportSourceVisitor {
+impl Visit for CollectImportSourceVisitor<'_> {
     fn visit_call_expr(&mut self, call_expr: &CallExpr) {
         // find import source from import('path/to/module')
-        // [NOTE]: Turbopack does not support webpack-specific comment directives, i.e
-        // import(/* webpackChunkName: 'hello1' */ '../../components/hello3')
-        // Renamed chunk in the comment will be ignored.
         if let Callee::Import(_import) = call_expr.callee {
             if let Some(arg) = call_expr.args.first() {
-                if let Expr::Lit(Lit::Str(str_)) = &*arg.expr {
-                    self.import_source = Some(str_.value.to_string());
+                match &*arg.expr {
+                    Expr::Lit(Lit::Str(str_)) => {
+                        let source = str_.value.to_string();
+                        self.import_source = Some(CollectedDynamicImport {
+                            pattern: Pattern::Constant(source.clone()),
+                            source,
+                            attributes: None,
+                            chunk_options: ChunkOptions::default(),
+                        });
+                    }
+                    Expr::Tpl(tpl) => {
+                        self.import_source = collect_template_literal_import(tpl);
+                    }
+                    _ => {}
+                }
+
+                if let Some(import_source) = &mut self.import_source {
+                    import_source.chunk_options =
+                        collect_webpack_magic_comment(self.comments, arg.expr.span().lo());
                 }
             }
+
+            if let Some(import_source) = &mut self.import_source {
+                import_source.attributes = collect_import_attributes(call_expr);
+            }
         }
 
         // Don't need to visit children, we expect import() won't have any
@@ -204,8 +442,216 @@ impl Visit for CollectImportSourceVisitor {
     }
 }
 
-pub type DynamicImportedModules = Vec<(String, Vc<Box<dyn Module>>)>;
-pub type DynamicImportedOutputAssets = Vec<(String, Vc<OutputAssets>)>;
+/// Builds a resolvable [Pattern] (and a display source string) from a template literal
+/// passed to `import()`, e.g. `` import(`./locales/${lang}.mjs`) ``. Each quasi becomes a
+/// `Pattern::Constant` and each interpolated expression its own `Pattern::Dynamic` token;
+/// how broadly a `Dynamic` token later resolves is up to `esm_resolve`, not this function.
+fn collect_template_literal_import(tpl: &Tpl) -> Option<CollectedDynamicImport> {
+    let mut source = String::new();
+    let mut parts = Vec::with_capacity(tpl.quasis.len() * 2 - 1);
+
+    for (i, quasi) in tpl.quasis.iter().enumerate() {
+        let raw = quasi.raw.as_str();
+        if !raw.is_empty() {
+            source.push_str(raw);
+            parts.push(Pattern::Constant(raw.to_string()));
+        }
+
+        if i < tpl.exprs.len() {
+            source.push('*');
+            parts.push(Pattern::Dynamic);
+        }
+    }
+
+    let pattern = match parts.len() {
+        0 => Pattern::Constant(String::new()),
+        1 => parts.remove(0),
+        _ => Pattern::Concatenation(parts),
+    };
+
+    Some(CollectedDynamicImport {
+        source,
+        pattern,
+        attributes: None,
+        chunk_options: ChunkOptions::default(),
+    })
+}
+
+/// Reads the leading comments attached to `pos` (the `import()` argument) and parses any
+/// Webpack-style magic comment directives off them, e.g.
+/// `import(/* webpackChunkName: "hello" */ '../x')`. Unrecognized directives are ignored.
+fn collect_webpack_magic_comment(
+    comments: &dyn Comments,
+    pos: turbopack_binding::swc::core::common::BytePos,
+) -> ChunkOptions {
+    let mut chunk_options = ChunkOptions::default();
+
+    let Some(leading) = comments.get_leading(pos) else {
+        return chunk_options;
+    };
+
+    for comment in leading.iter() {
+        for directive in comment.text.split(',') {
+            let Some((key, value)) = directive.split_once(':') else {
+                continue;
+            };
+
+            let value = value.trim().trim_matches(['\'', '"']).to_string();
+            match key.trim() {
+                "webpackChunkName" => chunk_options.chunk_name = Some(value),
+                "webpackMode" => chunk_options.mode = Some(value),
+                "webpackPrefetch" => chunk_options.prefetch = value == "true",
+                "webpackPreload" => chunk_options.preload = value == "true",
+                _ => {}
+            }
+        }
+    }
+
+    chunk_options
+}
+
+/// Reads the `with`/`assert` object off the second argument of an `import()` call, e.g.
+/// `import('./data.json', { with: { type: 'json' } })`.
+fn collect_import_attributes(call_expr: &CallExpr) -> Option<ImportAttributes> {
+    let arg = call_expr.args.get(1)?;
+    let Expr::Object(obj) = &*arg.expr else {
+        return None;
+    };
+
+    for prop in &obj.props {
+        if let PropOrSpread::Prop(prop) = prop {
+            if let Prop::KeyValue(kv) = &**prop {
+                if let PropName::Ident(key) = &kv.key {
+                    if key.sym == *"with" || key.sym == *"assert" {
+                        if let Expr::Object(attrs_obj) = &*kv.value {
+                            let mut attributes = ImportAttributes::new();
+                            for attr_prop in &attrs_obj.props {
+                                if let PropOrSpread::Prop(attr_prop) = attr_prop {
+                                    if let Prop::KeyValue(attr_kv) = &**attr_prop {
+                                        if let PropName::Ident(attr_key) = &attr_kv.key {
+                                            if let Expr::Lit(Lit::Str(str_)) = &*attr_kv.value {
+                                                attributes.insert(
+                                                    attr_key.sym.to_string(),
+                                                    str_.value.to_string(),
+                                                );
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+
+                            return (!attributes.is_empty()).then_some(attributes);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// A single `import(...)` call captured from inside a `dynamic()` wrapper, along with the
+/// [Pattern] used to resolve it (a plain string literal resolves to a single
+/// `Pattern::Constant`, a template literal may resolve to several matching modules).
+#[derive(Clone, Debug)]
+struct CollectedDynamicImport {
+    /// The import specifier as written in source, used for display and as the map key for
+    /// the resulting chunk(s).
+    source: String,
+    pattern: Pattern,
+    /// The `with`/`assert` attributes passed as the second argument to `import()`, if any.
+    attributes: Option<ImportAttributes>,
+    /// Webpack magic comment directives attached to the `import()` argument, if any.
+    chunk_options: ChunkOptions,
+}
+
+/// Webpack-style magic comment directives captured off a dynamic `import()` call, e.g.
+/// `import(/* webpackChunkName: "hello", webpackPrefetch: true */ '../x')`.
+#[derive(Clone, Debug, Default)]
+pub struct ChunkOptions {
+    /// The author-specified chunk name (`webpackChunkName`), used instead of an
+    /// auto-generated one when present.
+    pub chunk_name: Option<String>,
+    pub mode: Option<String>,
+    pub prefetch: bool,
+    pub preload: bool,
+}
+
+/// Import attributes (or the legacy `assert` syntax) captured off a dynamic `import()`
+/// call, e.g. `{ type: "json" }` from `import('./x.json', { with: { type: 'json' } })`.
+pub type ImportAttributes = IndexMap<String, String>;
+
+pub type DynamicImportedModules =
+    Vec<(String, Option<ImportAttributes>, ChunkOptions, Vc<Box<dyn Module>>)>;
+/// Per dynamic-import chunk group: the name (`chunk_options.chunk_name` if the author set
+/// one via `webpackChunkName`, else the raw import source - see
+/// [collect_next_dynamic_chunks]), the [ChunkOptions] it was built from, and its assets.
+pub type DynamicImportedOutputAssets = Vec<(String, ChunkOptions, Vc<OutputAssets>)>;
+
+/// A project-level import map used to rewrite bare/aliased dynamic-import specifiers
+/// before they're resolved (<https://github.com/WICG/import-maps>): top-level `imports`
+/// plus per-path-prefix `scopes` that override them for importers under that scope.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Default, Clone)]
+pub struct DynamicImportMap {
+    imports: IndexMap<String, String>,
+    scopes: IndexMap<String, IndexMap<String, String>>,
+}
+
+impl DynamicImportMap {
+    pub fn new(
+        imports: IndexMap<String, String>,
+        scopes: IndexMap<String, IndexMap<String, String>>,
+    ) -> Self {
+        Self { imports, scopes }
+    }
+
+    /// Resolves `specifier`, as imported from `importer`, against this import map.
+    /// Returns `specifier` unchanged if nothing in the map applies.
+    fn resolve<'a>(&'a self, importer: &str, specifier: &'a str) -> Cow<'a, str> {
+        // A scope whose prefix matches the importer wins over the top-level `imports`;
+        // the longest matching scope prefix wins among scopes.
+        let scoped_imports = self
+            .scopes
+            .iter()
+            .filter(|(prefix, _)| importer.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, imports)| imports);
+
+        if let Some(rewritten) = scoped_imports.and_then(|imports| Self::apply(imports, specifier))
+        {
+            return Cow::Owned(rewritten);
+        }
+
+        Self::apply(&self.imports, specifier)
+            .map(Cow::Owned)
+            .unwrap_or(Cow::Borrowed(specifier))
+    }
+
+    /// Exact matches win outright; otherwise the longest matching trailing-slash prefix
+    /// key rewrites the leading portion of the specifier, e.g. `"#components/"` remaps
+    /// `"#components/hero"` to `"<target>hero"`.
+    fn apply(imports: &IndexMap<String, String>, specifier: &str) -> Option<String> {
+        if let Some(target) = imports.get(specifier) {
+            return Some(target.clone());
+        }
+
+        imports
+            .iter()
+            .filter(|(key, _)| key.ends_with('/') && specifier.starts_with(key.as_str()))
+            .max_by_key(|(key, _)| key.len())
+            .map(|(key, target)| format!("{target}{}", &specifier[key.len()..]))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl DynamicImportMap {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        Self::default().cell()
+    }
+}
 
 /// A struct contains mapping for the dynamic imports to construct chunk per
 /// each individual module (Origin Module, Vec<(ImportSourceString, Module)>)
@@ -224,6 +670,8 @@ impl OptionDynamicImportsMap {
     }
 }
 
+/// Maps each origin module to the output chunks generated for its dynamic imports, as
+/// built by [collect_next_dynamic_chunks].
 #[turbo_tasks::value(transparent)]
 pub struct DynamicImportedChunks(pub IndexMap<Vc<Box<dyn Module>>, DynamicImportedOutputAssets>);
 
@@ -271,3 +719,391 @@ impl Issue for NextDynamicParsingIssue {
         )
     }
 }
+
+/// An issue raised when a dynamic `import()` requests an import attribute `type` that
+/// Turbopack doesn't know how to resolve, e.g. `import('./x.foo', { with: { type: 'foo' } })`.
+#[turbo_tasks::value(shared)]
+pub struct UnsupportedImportAttributeIssue {
+    ident: Vc<AssetIdent>,
+    attribute_type: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for UnsupportedImportAttributeIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell("Unsupported import attribute".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("parsing".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.ident.path()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "Requested an unsupported import attribute type \"{}\". Only \"json\" is \
+             currently resolved; the import will be resolved as if no attribute was given.",
+            self.attribute_type
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn detail(&self) -> Vc<String> {
+        Vc::cell(
+            "Import attributes (and the legacy `assert` syntax) are used to pick a module \
+             type for the resolved import. Remove the attribute or use a supported type."
+                .to_string(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use turbopack_binding::swc::core::{
+        common::{comments::SingleThreadedComments, FileName, SourceMap},
+        ecma::{
+            ast::{Module as EcmaModule, Program},
+            parser::{lexer::Lexer, EsConfig, Parser, StringInput, Syntax},
+        },
+    };
+
+    use super::*;
+
+    fn parse(src: &str) -> (EcmaModule, SingleThreadedComments) {
+        let cm: Arc<SourceMap> = Default::default();
+        let fm = cm.new_source_file(FileName::Anon, src.to_string());
+        let comments = SingleThreadedComments::default();
+        let lexer = Lexer::new(
+            Syntax::Es(EsConfig {
+                ..Default::default()
+            }),
+            Default::default(),
+            StringInput::from(&*fm),
+            Some(&comments),
+        );
+        let module = match Parser::new_from(lexer).parse_program().unwrap() {
+            Program::Module(module) => module,
+            Program::Script(_) => panic!("expected a module"),
+        };
+        (module, comments)
+    }
+
+    fn collect_dynamic_imports(src: &str) -> Vec<CollectedDynamicImport> {
+        let (module, comments) = parse(src);
+        let mut visitor = LodableImportVisitor::new(&comments);
+        module.visit_with(&mut visitor);
+        visitor.import_sources
+    }
+
+    #[test]
+    fn collect_template_literal_import_no_interpolation() {
+        let imports =
+            collect_dynamic_imports("import dynamic from 'next/dynamic';\ndynamic(() => import(`./fixed.js`));");
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].source, "./fixed.js");
+        assert_eq!(imports[0].pattern, Pattern::Constant("./fixed.js".to_string()));
+    }
+
+    #[test]
+    fn collect_template_literal_import_leading_interpolation() {
+        let imports = collect_dynamic_imports(
+            "import dynamic from 'next/dynamic';\ndynamic(() => import(`${lang}/index.js`));",
+        );
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].source, "*/index.js");
+        assert_eq!(
+            imports[0].pattern,
+            Pattern::Concatenation(vec![
+                Pattern::Dynamic,
+                Pattern::Constant("/index.js".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn collect_template_literal_import_trailing_interpolation() {
+        let imports = collect_dynamic_imports(
+            "import dynamic from 'next/dynamic';\ndynamic(() => import(`./locales/${lang}`));",
+        );
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].source, "./locales/*");
+        assert_eq!(
+            imports[0].pattern,
+            Pattern::Concatenation(vec![
+                Pattern::Constant("./locales/".to_string()),
+                Pattern::Dynamic,
+            ])
+        );
+    }
+
+    #[test]
+    fn collect_template_literal_import_adjacent_interpolations() {
+        let imports = collect_dynamic_imports(
+            "import dynamic from 'next/dynamic';\ndynamic(() => import(`${a}${b}`));",
+        );
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].source, "**");
+        assert_eq!(
+            imports[0].pattern,
+            Pattern::Concatenation(vec![Pattern::Dynamic, Pattern::Dynamic])
+        );
+    }
+
+    #[test]
+    fn collect_template_literal_import_interpolation_followed_by_path_segment() {
+        // A `Pattern::Dynamic` token is emitted per interpolation regardless of what
+        // follows it in the template - this function doesn't special-case (or need to
+        // guard against) a later `/`, since it never inspects the interpolated
+        // expression's runtime value. Whether the resolved value may itself contain a
+        // `/` is entirely up to `esm_resolve`, not this function.
+        let imports = collect_dynamic_imports(
+            "import dynamic from 'next/dynamic';\ndynamic(() => import(`./pages/${path}/index.js`));",
+        );
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].source, "./pages/*/index.js");
+        assert_eq!(
+            imports[0].pattern,
+            Pattern::Concatenation(vec![
+                Pattern::Constant("./pages/".to_string()),
+                Pattern::Dynamic,
+                Pattern::Constant("/index.js".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn collect_webpack_magic_comment_multiple_directives() {
+        let imports = collect_dynamic_imports(
+            "import dynamic from 'next/dynamic';\n\
+             dynamic(() => import(/* webpackChunkName: \"hero\", webpackPrefetch: true */ './hero'));",
+        );
+        assert_eq!(imports.len(), 1);
+        let options = &imports[0].chunk_options;
+        assert_eq!(options.chunk_name.as_deref(), Some("hero"));
+        assert!(options.prefetch);
+        assert!(!options.preload);
+    }
+
+    #[test]
+    fn collect_webpack_magic_comment_separate_comments() {
+        let imports = collect_dynamic_imports(
+            "import dynamic from 'next/dynamic';\n\
+             dynamic(() => import(\
+                /* webpackChunkName: 'hero' */ \
+                /* webpackMode: 'lazy' */ \
+                /* webpackPreload: true */ \
+                './hero'\
+             ));",
+        );
+        assert_eq!(imports.len(), 1);
+        let options = &imports[0].chunk_options;
+        assert_eq!(options.chunk_name.as_deref(), Some("hero"));
+        assert_eq!(options.mode.as_deref(), Some("lazy"));
+        assert!(options.preload);
+        assert!(!options.prefetch);
+    }
+
+    #[test]
+    fn collect_webpack_magic_comment_ignores_unknown_directives() {
+        let imports = collect_dynamic_imports(
+            "import dynamic from 'next/dynamic';\n\
+             dynamic(() => import(/* webpackSomethingElse: 'x' */ './hero'));",
+        );
+        assert_eq!(imports.len(), 1);
+        let options = &imports[0].chunk_options;
+        assert_eq!(options.chunk_name, None);
+        assert_eq!(options.mode, None);
+    }
+
+    #[test]
+    fn collect_import_attributes_with_json_type() {
+        let imports = collect_dynamic_imports(
+            "import dynamic from 'next/dynamic';\n\
+             dynamic(() => import('./data.json', { with: { type: 'json' } }));",
+        );
+        assert_eq!(imports.len(), 1);
+        let attributes = imports[0].attributes.as_ref().expect("attributes present");
+        assert_eq!(attributes.get("type").map(String::as_str), Some("json"));
+    }
+
+    #[test]
+    fn collect_import_attributes_legacy_assert() {
+        let imports = collect_dynamic_imports(
+            "import dynamic from 'next/dynamic';\n\
+             dynamic(() => import('./data.json', { assert: { type: 'json' } }));",
+        );
+        assert_eq!(imports.len(), 1);
+        let attributes = imports[0].attributes.as_ref().expect("attributes present");
+        assert_eq!(attributes.get("type").map(String::as_str), Some("json"));
+    }
+
+    #[test]
+    fn collect_import_attributes_unsupported_type_still_captured() {
+        // Unsupported attribute types are still captured here; it's
+        // `build_dynamic_imports_map_for_module` that decides to emit a warning and fall
+        // back to `Undefined` when resolving.
+        let imports = collect_dynamic_imports(
+            "import dynamic from 'next/dynamic';\n\
+             dynamic(() => import('./data.foo', { with: { type: 'foo' } }));",
+        );
+        assert_eq!(imports.len(), 1);
+        let attributes = imports[0].attributes.as_ref().expect("attributes present");
+        assert_eq!(attributes.get("type").map(String::as_str), Some("foo"));
+    }
+
+    #[test]
+    fn collect_import_attributes_none_without_second_arg() {
+        let imports = collect_dynamic_imports(
+            "import dynamic from 'next/dynamic';\ndynamic(() => import('./plain'));",
+        );
+        assert_eq!(imports.len(), 1);
+        assert!(imports[0].attributes.is_none());
+    }
+
+    fn import_map(
+        imports: &[(&str, &str)],
+        scopes: &[(&str, &[(&str, &str)])],
+    ) -> DynamicImportMap {
+        DynamicImportMap::new(
+            imports
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            scopes
+                .iter()
+                .map(|(scope, entries)| {
+                    (
+                        scope.to_string(),
+                        entries
+                            .iter()
+                            .map(|(k, v)| (k.to_string(), v.to_string()))
+                            .collect(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn dynamic_import_map_exact_match_wins_over_prefix() {
+        let map = import_map(
+            &[
+                ("#components/hero", "./exact-hero.js"),
+                ("#components/", "./components/"),
+            ],
+            &[],
+        );
+        assert_eq!(
+            map.resolve("/app/page.tsx", "#components/hero"),
+            "./exact-hero.js"
+        );
+    }
+
+    #[test]
+    fn dynamic_import_map_longest_prefix_wins() {
+        let map = import_map(
+            &[
+                ("#components/", "./components/"),
+                ("#components/ui/", "./ui/"),
+                ("#/", "./src/"),
+            ],
+            &[],
+        );
+        assert_eq!(
+            map.resolve("/app/page.tsx", "#components/ui/button"),
+            "./ui/button"
+        );
+    }
+
+    #[test]
+    fn dynamic_import_map_unmatched_specifier_is_unchanged() {
+        let map = import_map(&[("#components/", "./components/")], &[]);
+        assert_eq!(map.resolve("/app/page.tsx", "#other/thing"), "#other/thing");
+    }
+
+    #[test]
+    fn dynamic_import_map_scope_overrides_top_level() {
+        let map = import_map(
+            &[("#lib/", "./lib/")],
+            &[("/app/admin/", &[("#lib/", "./admin-lib/")])],
+        );
+        assert_eq!(
+            map.resolve("/app/admin/page.tsx", "#lib/util"),
+            "./admin-lib/util"
+        );
+        // Outside the scope, the top-level mapping still applies.
+        assert_eq!(map.resolve("/app/page.tsx", "#lib/util"), "./lib/util");
+    }
+
+    #[test]
+    fn dynamic_import_map_longest_matching_scope_wins() {
+        let map = import_map(
+            &[],
+            &[
+                ("/app/", &[("#lib/", "./outer-lib/")]),
+                ("/app/admin/", &[("#lib/", "./inner-lib/")]),
+            ],
+        );
+        assert_eq!(
+            map.resolve("/app/admin/page.tsx", "#lib/util"),
+            "./inner-lib/util"
+        );
+    }
+
+    #[test]
+    fn tarjan_scc_orders_linear_chain_reverse_topologically() {
+        // 0 -> 1 -> 2
+        let edges = vec![vec![1], vec![2], vec![]];
+        let components = tarjan_scc(&edges, 0);
+        assert_eq!(components, vec![vec![2], vec![1], vec![0]]);
+    }
+
+    #[test]
+    fn tarjan_scc_groups_a_cycle_into_one_component() {
+        // 0 -> 1 -> 2 -> 1 (1 and 2 form a cycle)
+        let edges = vec![vec![1], vec![2], vec![1]];
+        let components = tarjan_scc(&edges, 0);
+        assert_eq!(components.len(), 2);
+        let mut cyclic = components[0].clone();
+        cyclic.sort();
+        assert_eq!(cyclic, vec![1, 2]);
+        assert_eq!(components[1], vec![0]);
+    }
+
+    #[test]
+    fn tarjan_scc_diamond_visits_shared_dependency_once() {
+        // 0 -> 1, 0 -> 2, 1 -> 3, 2 -> 3
+        let edges = vec![vec![1, 2], vec![3], vec![3], vec![]];
+        let components = tarjan_scc(&edges, 0);
+        let flattened: Vec<_> = components.into_iter().flatten().collect();
+        assert_eq!(flattened.len(), 4);
+        // The shared dependency must finish (and be emitted) before both of its
+        // dependents.
+        let pos = |node: usize| flattened.iter().position(|&n| n == node).unwrap();
+        assert!(pos(3) < pos(1));
+        assert!(pos(3) < pos(2));
+        assert!(pos(1) < pos(0));
+        assert!(pos(2) < pos(0));
+    }
+
+    #[test]
+    fn tarjan_scc_self_loop_is_its_own_component() {
+        let edges = vec![vec![0]];
+        let components = tarjan_scc(&edges, 0);
+        assert_eq!(components, vec![vec![0]]);
+    }
+}